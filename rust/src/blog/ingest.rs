@@ -0,0 +1,188 @@
+//! Publish/unpublish webhook, called by Django whenever a
+//! `PublishedSnapshot` is ingested (published or retracted).
+//!
+//! This is what actually keeps the embedded search index in sync with
+//! what's live: publishing (re)indexes the post and persists the index;
+//! unpublishing drops it and persists again.
+
+use std::path::PathBuf;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::Json;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::blog::{Block, BlogCategory, BlogPostDetail, PublishedSnapshot};
+use crate::AppState;
+
+use super::social::{self, AnnouncementLog, MastodonConfig};
+
+/// Path the search index is persisted to, overridable via
+/// `SEARCH_INDEX_PATH` - the same path `main.rs` loads from at startup.
+pub fn search_index_path() -> PathBuf {
+    PathBuf::from(std::env::var("SEARCH_INDEX_PATH").unwrap_or_else(|_| "search-index.json".to_string()))
+}
+
+#[derive(Debug, FromRow)]
+struct PostRow {
+    slug: String,
+    title: String,
+    excerpt: String,
+    featured_image_url: Option<String>,
+    published_at: Option<DateTime<Utc>>,
+    reading_time_minutes: Option<i32>,
+    tags: Vec<String>,
+    seo_title: String,
+    seo_description: String,
+    og_image_url: String,
+    category_id: Option<Uuid>,
+    category_name: Option<String>,
+    category_slug: Option<String>,
+    category_description: Option<String>,
+    category_color: Option<String>,
+    category_sort_order: Option<i32>,
+}
+
+#[derive(Debug, FromRow)]
+struct BlockRow {
+    id: String,
+    block_type: String,
+    sequence: i32,
+    data: serde_json::Value,
+}
+
+/// Load the full `BlogPostDetail` for a slug, the same shape the
+/// detail page renders, so the search index gets title/excerpt/category/
+/// tags/blocks - not just what's in the (much thinner) `PublishedSnapshot`.
+async fn load_post_detail(db: &PgPool, slug: &str) -> sqlx::Result<Option<BlogPostDetail>> {
+    let post = sqlx::query_as::<_, PostRow>(
+        r#"
+        SELECT p.slug, p.title, p.excerpt, p.featured_image_url, p.published_at,
+               p.reading_time_minutes, p.tags, p.seo_title, p.seo_description, p.og_image_url,
+               c.id AS category_id, c.name AS category_name, c.slug AS category_slug,
+               c.description AS category_description, c.color AS category_color,
+               c.sort_order AS category_sort_order
+        FROM blog_post p
+        LEFT JOIN blog_category c ON c.id = p.category_id
+        WHERE p.slug = $1
+        "#,
+    )
+    .bind(slug)
+    .fetch_optional(db)
+    .await?;
+
+    let Some(post) = post else {
+        return Ok(None);
+    };
+
+    let blocks = sqlx::query_as::<_, BlockRow>(
+        r#"SELECT id, "type" AS block_type, sequence, data FROM blog_block WHERE post_slug = $1 ORDER BY sequence"#,
+    )
+    .bind(slug)
+    .fetch_all(db)
+    .await?;
+
+    let category = post.category_id.map(|id| BlogCategory {
+        id,
+        name: post.category_name.unwrap_or_default(),
+        slug: post.category_slug.unwrap_or_default(),
+        description: post.category_description.unwrap_or_default(),
+        color: post.category_color.unwrap_or_default(),
+        sort_order: post.category_sort_order.unwrap_or_default(),
+    });
+
+    Ok(Some(BlogPostDetail {
+        slug: post.slug,
+        title: post.title,
+        excerpt: post.excerpt,
+        featured_image_url: post.featured_image_url,
+        category,
+        published_at: post.published_at,
+        reading_time_minutes: post.reading_time_minutes,
+        tags: post.tags,
+        seo_title: post.seo_title,
+        seo_description: post.seo_description,
+        og_image_url: post.og_image_url,
+        blocks: blocks
+            .into_iter()
+            .map(|b| Block {
+                id: b.id,
+                block_type: b.block_type,
+                sequence: b.sequence,
+                data: b.data,
+            })
+            .collect(),
+    }))
+}
+
+/// `POST /internal/blog/publish` - body is the `PublishedSnapshot` Django
+/// just wrote. Loads the full post detail and (re)indexes it.
+pub async fn publish(
+    State(state): State<AppState>,
+    Json(snapshot): Json<PublishedSnapshot>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let slug = snapshot.meta.slug.clone();
+
+    let detail = load_post_detail(&state.db, &slug)
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to load post {} for indexing: {}", slug, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "failed to load post" })),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "post not found" })),
+            )
+        })?;
+
+    {
+        let mut index = state
+            .search_index
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        index.index_post(&detail);
+
+        if let Err(e) = index.persist(&search_index_path()) {
+            tracing::error!("failed to persist search index: {}", e);
+        }
+    }
+
+    if let Some(config) = MastodonConfig::from_env() {
+        let base_url = std::env::var("BASE_URL").unwrap_or_else(|_| "https://happydiving.mx".to_string());
+        let mut log = AnnouncementLog::load_or_default(&social::announcement_log_path());
+
+        match social::announce(&config, &mut log, &base_url, &snapshot).await {
+            Ok(status_url) => {
+                tracing::info!("announced {} on mastodon: {}", slug, status_url);
+                if let Err(e) = log.persist(&social::announcement_log_path()) {
+                    tracing::error!("failed to persist announcement log: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("failed to announce {} on mastodon: {}", slug, e),
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /internal/blog/unpublish/:slug` - drop a retracted post from
+/// the search index.
+pub async fn unpublish(State(state): State<AppState>, Path(slug): Path<String>) -> StatusCode {
+    let mut index = state
+        .search_index
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    index.remove(&slug);
+
+    if let Err(e) = index.persist(&search_index_path()) {
+        tracing::error!("failed to persist search index: {}", e);
+    }
+
+    StatusCode::NO_CONTENT
+}
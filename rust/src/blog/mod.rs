@@ -0,0 +1,12 @@
+//! Blog full-text search, OG image generation, and social announcements.
+
+pub mod ingest;
+pub mod og_image;
+mod routes;
+pub mod search;
+pub mod social;
+
+pub use ingest::{publish as publish_handler, search_index_path, unpublish as unpublish_handler};
+pub use og_image::OgImageCache;
+pub use routes::{og_image as og_image_handler, search_handler, SearchState};
+pub use search::SearchIndex;
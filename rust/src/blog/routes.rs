@@ -0,0 +1,112 @@
+//! HTTP handlers for blog full-text search and OG image generation.
+
+use std::sync::{Arc, RwLock};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::AppState;
+
+use super::search::SearchIndex;
+
+/// Shared, lock-protected search index, held in `AppState`.
+pub type SearchState = Arc<RwLock<SearchIndex>>;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    q: String,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    query: String,
+    results: Vec<super::search::SearchHit>,
+}
+
+/// `GET /blog/search?q=...&limit=...`
+pub async fn search_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Json<SearchResponse> {
+    let results = state
+        .search_index
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .search(&params.q, params.limit);
+
+    Json(SearchResponse { query: params.q, results })
+}
+
+#[derive(Debug, FromRow)]
+struct OgImageRow {
+    title: String,
+    category_name: Option<String>,
+    category_color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OgImageParams {
+    /// Snapshot version, for cache-busting; Django bumps this on every
+    /// re-publish so an edited post gets a freshly rendered card.
+    #[serde(default, rename = "v")]
+    version: i32,
+}
+
+/// `GET /blog/:slug/og.png?v=<snapshot version>`
+pub async fn og_image(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(params): Query<OgImageParams>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let row = sqlx::query_as::<_, OgImageRow>(
+        r#"
+        SELECT p.title, c.name AS category_name, c.color AS category_color
+        FROM blog_post p
+        LEFT JOIN blog_category c ON c.id = p.category_id
+        WHERE p.slug = $1 AND p.published_at IS NOT NULL
+        "#,
+    )
+    .bind(&slug)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("failed to load post for og image: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "failed to load post" })),
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "post not found" })),
+        )
+    })?;
+
+    let png = super::og_image::render_cached(
+        &state.og_image_cache,
+        &slug,
+        params.version,
+        &row.title,
+        row.category_name.as_deref().unwrap_or("Blog"),
+        row.category_color.as_deref().unwrap_or("#2563eb"),
+    )
+    .map_err(|e| {
+        tracing::error!("failed to render og image: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "failed to render og image" })),
+        )
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], png))
+}
@@ -0,0 +1,316 @@
+//! Embedded full-text search index over blog content, ranked with BM25.
+//!
+//! Each `BlogPostDetail` is flattened into a `Document` (title, excerpt,
+//! category, tags, and the rendered text of every block) and indexed by
+//! slug. Reindexing a single slug only touches that document, so
+//! republishing one post doesn't require rebuilding the whole index.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::blog::BlogPostDetail;
+
+/// BM25 tuning parameters (standard defaults).
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// A single indexed document: enough of the original text to build a
+/// highlighted snippet, its length for BM25 normalization, and the set of
+/// terms it contributed to `SearchIndex::postings` (so `remove` and
+/// reindexing know what to clean up there).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Document {
+    slug: String,
+    title: String,
+    excerpt: String,
+    token_count: usize,
+    terms: Vec<String>,
+}
+
+/// A search result: the matching slug, its BM25 score, and a snippet.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub slug: String,
+    pub title: String,
+    pub score: f64,
+    pub snippet: String,
+}
+
+/// In-memory inverted index over indexed blog posts: `postings` maps each
+/// term to the slugs that contain it (with their per-document term count),
+/// so a query only scores the documents that actually contain one of its
+/// terms instead of scanning every indexed post.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    documents: HashMap<String, Document>,
+    postings: HashMap<String, HashMap<String, usize>>,
+}
+
+/// Lowercase and split on anything that isn't alphanumeric.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Flatten a post's blocks (rich text, headings, hero, CTA, ...) into one
+/// searchable string, reusing the same accessors the templates use.
+fn block_text(post: &BlogPostDetail) -> String {
+    post.blocks
+        .iter()
+        .map(|b| {
+            [b.content(), b.text(), b.title(), b.subtitle(), b.cta_text(), b.caption()].join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a previously persisted index, or start empty if none exists yet.
+    pub fn load_or_default(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Persist the index to disk so restarts don't have to reindex everything.
+    pub fn persist(&self, path: &Path) -> io::Result<()> {
+        let raw = serde_json::to_string(self).unwrap_or_default();
+        fs::write(path, raw)
+    }
+
+    /// Index (or reindex) a single post by slug. Safe to call repeatedly as
+    /// posts are republished - only that slug's entry (and its postings) is
+    /// touched.
+    pub fn index_post(&mut self, post: &BlogPostDetail) {
+        // Drop any postings left over from a previous version of this post
+        // first, so republishing with different content doesn't leave stale
+        // term -> slug entries behind.
+        self.remove(&post.slug);
+
+        let category_name = post
+            .category
+            .as_ref()
+            .map(|c| c.name.as_str())
+            .unwrap_or("");
+
+        let text = [
+            post.title.as_str(),
+            post.excerpt.as_str(),
+            category_name,
+            &post.tags.join(" "),
+            &block_text(post),
+        ]
+        .join(" ");
+
+        let tokens = tokenize(&text);
+        let mut term_counts: HashMap<String, usize> = HashMap::new();
+        for token in &tokens {
+            *term_counts.entry(token.clone()).or_insert(0) += 1;
+        }
+
+        for (term, count) in &term_counts {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(post.slug.clone(), *count);
+        }
+
+        self.documents.insert(
+            post.slug.clone(),
+            Document {
+                slug: post.slug.clone(),
+                title: post.title.clone(),
+                excerpt: post.excerpt.clone(),
+                token_count: tokens.len(),
+                terms: term_counts.into_keys().collect(),
+            },
+        );
+    }
+
+    /// Drop a post from the index (e.g. when unpublished), along with every
+    /// postings entry it contributed.
+    pub fn remove(&mut self, slug: &str) {
+        let Some(doc) = self.documents.remove(slug) else {
+            return;
+        };
+        for term in &doc.terms {
+            if let Some(postings) = self.postings.get_mut(term) {
+                postings.remove(slug);
+                if postings.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+    }
+
+    fn avg_doc_len(&self) -> f64 {
+        if self.documents.is_empty() {
+            return 0.0;
+        }
+        let total: usize = self.documents.values().map(|d| d.token_count).sum();
+        total as f64 / self.documents.len() as f64
+    }
+
+    /// Number of indexed documents containing `term` at least once.
+    fn doc_freq(&self, term: &str) -> usize {
+        self.postings.get(term).map(|slugs| slugs.len()).unwrap_or(0)
+    }
+
+    /// Search the index and return the top `limit` matches, ranked by BM25.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() || self.documents.is_empty() {
+            return vec![];
+        }
+
+        let n = self.documents.len() as f64;
+        let avg_doc_len = self.avg_doc_len();
+
+        // idf per unique query term, computed once
+        let idf: HashMap<&str, f64> = query_terms
+            .iter()
+            .map(|t| {
+                let df = self.doc_freq(t) as f64;
+                (t.as_str(), ((n - df + 0.5) / (df + 0.5) + 1.0).ln())
+            })
+            .collect();
+
+        // Walk the postings for each query term instead of every indexed
+        // document, so a query only touches documents that actually contain
+        // at least one of its terms.
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        for term in &query_terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let idf_t = idf[term.as_str()];
+            for (slug, &tf) in postings {
+                let doc = &self.documents[slug];
+                let tf = tf as f64;
+                let score = idf_t * (tf * (K1 + 1.0))
+                    / (tf + K1 * (1.0 - B + B * doc.token_count as f64 / avg_doc_len.max(1.0)));
+                *scores.entry(slug.as_str()).or_insert(0.0) += score;
+            }
+        }
+
+        let mut scored: Vec<(f64, &Document)> = scores
+            .into_iter()
+            .map(|(slug, score)| (score, &self.documents[slug]))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(score, doc)| SearchHit {
+                slug: doc.slug.clone(),
+                title: doc.title.clone(),
+                score,
+                snippet: snippet(&doc.excerpt, &query_terms),
+            })
+            .collect()
+    }
+}
+
+/// Build a short highlighted snippet from a post's excerpt, wrapping any
+/// matched query term in `**…**`.
+fn snippet(excerpt: &str, query_terms: &[String]) -> String {
+    let mut snippet = excerpt.to_string();
+    for term in query_terms {
+        let lower = snippet.to_lowercase();
+        if let Some(idx) = lower.find(term.as_str()) {
+            let end = idx + term.len();
+            snippet = format!("{}**{}**{}", &snippet[..idx], &snippet[idx..end], &snippet[end..]);
+        }
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(slug: &str, title: &str, excerpt: &str) -> BlogPostDetail {
+        BlogPostDetail {
+            slug: slug.to_string(),
+            title: title.to_string(),
+            excerpt: excerpt.to_string(),
+            featured_image_url: None,
+            category: None,
+            published_at: None,
+            reading_time_minutes: None,
+            tags: vec![],
+            seo_title: String::new(),
+            seo_description: String::new(),
+            og_image_url: String::new(),
+            blocks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_search_finds_matching_document() {
+        let mut index = SearchIndex::new();
+        index.index_post(&doc("wreck-diving", "Wreck Diving Basics", "Learn the fundamentals of wreck penetration"));
+        index.index_post(&doc("night-diving", "Night Diving Tips", "Using lights safely after dark"));
+
+        let hits = index.search("wreck", 10);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].slug, "wreck-diving");
+    }
+
+    #[test]
+    fn test_search_ranks_higher_term_frequency_first() {
+        // Both docs contain "buceo" (so idf is identical for each), but "a"
+        // repeats it far more - BM25 should rank it first with a higher score.
+        let mut index = SearchIndex::new();
+        index.index_post(&doc("a", "Buceo", "buceo buceo buceo buceo"));
+        index.index_post(&doc("b", "Otro", "buceo una vez"));
+
+        let hits = index.search("buceo", 10);
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].slug, "a");
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn test_remove_drops_document_from_results() {
+        let mut index = SearchIndex::new();
+        index.index_post(&doc("wreck-diving", "Wreck Diving Basics", "wreck penetration"));
+        index.remove("wreck-diving");
+
+        assert!(index.search("wreck", 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_empty_index_returns_no_hits() {
+        let index = SearchIndex::new();
+        assert!(index.search("anything", 10).is_empty());
+    }
+
+    #[test]
+    fn test_reindexing_a_post_drops_stale_postings() {
+        // Republishing with different content shouldn't leave the old
+        // version's terms pointing at this slug in the postings map.
+        let mut index = SearchIndex::new();
+        index.index_post(&doc("wreck-diving", "Wreck Diving Basics", "wreck penetration"));
+        index.index_post(&doc("wreck-diving", "Night Diving Tips", "dark water navigation"));
+
+        assert!(index.search("wreck", 10).is_empty());
+        assert_eq!(index.search("navigation", 10).len(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+//! Mastodon/Fediverse announcements for newly published blog posts.
+//!
+//! When a `PublishedSnapshot` is first published, post a toot built from
+//! its `PageMeta` (title, SEO description, canonical URL) with the
+//! `og_image_url` attached as media, using the megalodon API shape:
+//! upload the media first, then create a status referencing the
+//! returned media id. Announcements are deduplicated by `slug` +
+//! snapshot `version` and persisted to disk, the same way `SearchIndex`
+//! is, so a restart (or a re-publish of an already-announced version)
+//! never double-posts.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::blog::{PageMeta, PublishedSnapshot};
+
+/// Path the announcement log is persisted to, overridable via
+/// `ANNOUNCEMENT_LOG_PATH`.
+pub fn announcement_log_path() -> PathBuf {
+    PathBuf::from(std::env::var("ANNOUNCEMENT_LOG_PATH").unwrap_or_else(|_| "mastodon-announcements.json".to_string()))
+}
+
+/// Mastodon instance configuration, read from the environment.
+#[derive(Debug, Clone)]
+pub struct MastodonConfig {
+    pub instance_url: String,
+    pub access_token: String,
+}
+
+impl MastodonConfig {
+    /// Load from `MASTODON_INSTANCE_URL` / `MASTODON_ACCESS_TOKEN`.
+    /// Returns `None` if either is unset, in which case announcing is a
+    /// no-op (self-hosted instances without Mastodon configured).
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            instance_url: std::env::var("MASTODON_INSTANCE_URL").ok()?,
+            access_token: std::env::var("MASTODON_ACCESS_TOKEN").ok()?,
+        })
+    }
+}
+
+/// Announcement errors.
+#[derive(Debug)]
+pub enum AnnounceError {
+    Request(reqwest::Error),
+    UnexpectedResponse(String),
+}
+
+impl std::fmt::Display for AnnounceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "mastodon request failed: {}", e),
+            Self::UnexpectedResponse(body) => {
+                write!(f, "mastodon returned an unexpected response: {}", body)
+            }
+        }
+    }
+}
+
+impl From<reqwest::Error> for AnnounceError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+/// A single announcement already posted, so re-publishing the same
+/// snapshot version doesn't double-post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Announcement {
+    slug: String,
+    version: i32,
+    status_url: String,
+}
+
+/// Tracks which (slug, version) pairs have already been announced.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnnouncementLog {
+    posted: Vec<Announcement>,
+}
+
+impl AnnouncementLog {
+    /// Load a previously persisted log, or start empty if none exists yet.
+    pub fn load_or_default(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Persist the log to disk so restarts don't forget what was announced.
+    pub fn persist(&self, path: &Path) -> io::Result<()> {
+        let raw = serde_json::to_string(self).unwrap_or_default();
+        fs::write(path, raw)
+    }
+
+    /// Status URL already recorded for a (slug, version), if any.
+    fn status_url_for(&self, slug: &str, version: i32) -> Option<&str> {
+        self.posted
+            .iter()
+            .find(|a| a.slug == slug && a.version == version)
+            .map(|a| a.status_url.as_str())
+    }
+
+    fn record(&mut self, slug: &str, version: i32, status_url: String) {
+        self.posted.push(Announcement {
+            slug: slug.to_string(),
+            version,
+            status_url,
+        });
+    }
+}
+
+/// Build the toot text: title, SEO description, then the canonical URL.
+fn status_text(meta: &PageMeta, base_url: &str) -> String {
+    let canonical_url = format!("{}{}", base_url.trim_end_matches('/'), meta.path);
+    format!("{}\n\n{}\n\n{}", meta.title, meta.seo_description, canonical_url)
+}
+
+#[derive(Deserialize)]
+struct MediaResponse {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    url: String,
+}
+
+/// Upload `og_image_url` as media, returning the media id to attach to
+/// the status. Returns `None` if there's no image to attach.
+async fn upload_media(
+    client: &reqwest::Client,
+    config: &MastodonConfig,
+    og_image_url: &str,
+) -> Result<Option<String>, AnnounceError> {
+    if og_image_url.is_empty() {
+        return Ok(None);
+    }
+
+    let image_bytes = client.get(og_image_url).send().await?.bytes().await?;
+    let form = reqwest::multipart::Form::new()
+        .part("file", reqwest::multipart::Part::bytes(image_bytes.to_vec()));
+
+    let response = client
+        .post(format!("{}/api/v1/media", config.instance_url))
+        .bearer_auth(&config.access_token)
+        .multipart(form)
+        .send()
+        .await?;
+
+    let media: MediaResponse = response.json().await.map_err(AnnounceError::from)?;
+    Ok(Some(media.id))
+}
+
+/// Post the status, optionally attaching `media_id`, returning the
+/// published status's URL.
+async fn post_status(
+    client: &reqwest::Client,
+    config: &MastodonConfig,
+    text: &str,
+    media_id: Option<&str>,
+) -> Result<String, AnnounceError> {
+    let mut body = serde_json::json!({ "status": text });
+    if let Some(id) = media_id {
+        body["media_ids"] = serde_json::json!([id]);
+    }
+
+    let response = client
+        .post(format!("{}/api/v1/statuses", config.instance_url))
+        .bearer_auth(&config.access_token)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AnnounceError::UnexpectedResponse(body));
+    }
+
+    let status: StatusResponse = response.json().await.map_err(AnnounceError::from)?;
+    Ok(status.url)
+}
+
+/// Announce a freshly published snapshot on Mastodon, unless this
+/// (slug, version) was already announced. Returns the status URL
+/// (freshly posted, or the one already on record).
+pub async fn announce(
+    config: &MastodonConfig,
+    log: &mut AnnouncementLog,
+    base_url: &str,
+    snapshot: &PublishedSnapshot,
+) -> Result<String, AnnounceError> {
+    let slug = snapshot.meta.slug.clone();
+
+    if let Some(existing) = log.status_url_for(&slug, snapshot.version) {
+        return Ok(existing.to_string());
+    }
+
+    let client = reqwest::Client::new();
+    let media_id = upload_media(&client, config, &snapshot.meta.og_image_url).await?;
+    let text = status_text(&snapshot.meta, base_url);
+    let status_url = post_status(&client, config, &text, media_id.as_deref()).await?;
+
+    log.record(&slug, snapshot.version, status_url.clone());
+    Ok(status_url)
+}
@@ -0,0 +1,25 @@
+//! Response compression middleware.
+//!
+//! Wraps every response (Askama-rendered HTML pages, the deco
+//! `ValidateResponse` JSON, ...) with brotli/gzip negotiation driven by
+//! `tower_http`'s `async-compression`-backed `CompressionLayer`, picking
+//! whichever encoding the client's `Accept-Encoding` header prefers.
+//! A minimum-size cutoff keeps tiny responses uncompressed, since the
+//! framing overhead isn't worth it below a few hundred bytes.
+
+use tower_http::compression::{predicate::SizeAbove, CompressionLayer};
+
+/// Default minimum response size (bytes) before compression kicks in.
+/// Matches nginx's own `gzip_min_length` default.
+const DEFAULT_MIN_SIZE: u16 = 860;
+
+/// Build the compression layer, honoring `COMPRESSION_MIN_SIZE` (bytes)
+/// to override the default cutoff.
+pub fn layer() -> CompressionLayer<SizeAbove> {
+    let min_size = std::env::var("COMPRESSION_MIN_SIZE")
+        .ok()
+        .and_then(|s| s.parse::<u16>().ok())
+        .unwrap_or(DEFAULT_MIN_SIZE);
+
+    CompressionLayer::new().compress_when(SizeAbove::new(min_size))
+}
@@ -3,8 +3,10 @@
 //! Provides Bühlmann ZHL-16C decompression calculations as HTTP endpoints,
 //! eliminating subprocess overhead from the standalone binary.
 
+mod engine;
 mod models;
 mod routes;
+mod toxicity;
 mod validator;
 
 pub use routes::router;
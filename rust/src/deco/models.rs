@@ -19,21 +19,38 @@ pub struct Segment {
     pub depth_m: f64,
     /// Duration in minutes
     pub duration_min: f64,
+    /// Index into the combined gas list (bottom gas is index 0, followed by
+    /// `deco_gases` in order). Defaults to the bottom gas when omitted.
+    #[serde(default)]
+    pub gas_index: Option<usize>,
 }
 
 /// Request payload for deco validation.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ValidateRequest {
     /// Dive profile segments
     pub segments: Vec<Segment>,
-    /// Gas mix
+    /// Bottom gas mix
     pub gas: GasMix,
+    /// Additional travel/deco gases available for switching, referenced by
+    /// `Segment::gas_index` (1-based after the bottom gas at index 0)
+    #[serde(default)]
+    pub deco_gases: Vec<GasMix>,
     /// Gradient factor low (0.0-1.0)
     #[serde(default = "default_gf_low")]
     pub gf_low: f64,
     /// Gradient factor high (0.0-1.0)
     #[serde(default = "default_gf_high")]
     pub gf_high: f64,
+    /// Ambient surface pressure in millibars (defaults to sea level, 1013.25).
+    /// Lower values model altitude dives / mountain lakes.
+    #[serde(default)]
+    pub surface_pressure_mbar: Option<f64>,
+    /// When true, return a per-compartment tissue-loading trace after every
+    /// segment in `ValidateResponse::trace`. Omitted by default to keep the
+    /// happy-path payload small.
+    #[serde(default)]
+    pub trace: bool,
 }
 
 fn default_gf_low() -> f64 {
@@ -44,6 +61,38 @@ fn default_gf_high() -> f64 {
     0.85
 }
 
+/// Sea-level atmospheric pressure in millibars, used when no
+/// `surface_pressure_mbar` is supplied.
+pub const SEA_LEVEL_PRESSURE_MBAR: f64 = 1013.25;
+
+/// Valid range for `surface_pressure_mbar`, covering high-altitude lakes
+/// through mildly pressurized environments.
+pub const SURFACE_PRESSURE_RANGE_MBAR: std::ops::RangeInclusive<f64> = 500.0..=1100.0;
+
+/// Snapshot of a single ZHL-16C compartment's inert-gas loading.
+#[derive(Debug, Serialize)]
+pub struct CompartmentTrace {
+    /// Compartment number (1-16)
+    pub compartment: u8,
+    /// Nitrogen tension (bar)
+    pub n2_bar: f64,
+    /// Helium tension (bar)
+    pub he_bar: f64,
+    /// This compartment's individual ceiling (meters)
+    pub ceiling_m: f64,
+}
+
+/// Tissue-loading state after a single segment, for trace mode.
+#[derive(Debug, Serialize)]
+pub struct SegmentTrace {
+    /// Depth of the segment this snapshot was taken after (meters)
+    pub depth_m: f64,
+    /// Running time at the end of this segment (minutes)
+    pub runtime_min: f64,
+    /// Per-compartment tissue state
+    pub compartments: Vec<CompartmentTrace>,
+}
+
 /// Decompression stop information.
 #[derive(Debug, Serialize)]
 pub struct DecoStop {
@@ -51,6 +100,9 @@ pub struct DecoStop {
     pub depth_m: f64,
     /// Stop duration in minutes
     pub duration_min: f64,
+    /// The gas assumed to be breathed at this stop (the richest available
+    /// mix whose PO2 at this depth stays within the safe limit)
+    pub gas: GasMix,
 }
 
 /// Response payload from deco validation.
@@ -82,6 +134,18 @@ pub struct ValidateResponse {
     pub max_depth_m: f64,
     /// Total runtime in minutes
     pub runtime_min: f64,
+    /// Depths (meters) at which each gas came into use: the first segment's
+    /// depth for the starting gas, then every depth where the active gas changed
+    pub switch_depths_m: Vec<f64>,
+    /// Ambient surface pressure (millibars) actually applied
+    pub surface_pressure_mbar: f64,
+    /// Accumulated CNS oxygen toxicity, as a percentage of the single-exposure limit
+    pub cns_percent: f64,
+    /// Accumulated pulmonary oxygen toxicity units
+    pub otu: f64,
+    /// Per-segment tissue-loading trace, present only when `trace: true` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<SegmentTrace>>,
     /// SHA256 hash of input
     pub input_hash: String,
 
@@ -93,3 +157,25 @@ pub struct ValidateResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
+
+/// Request payload for batch deco validation: a list of independent profiles.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ValidateBatchRequest {
+    /// Dive profiles to validate, each with its own segments, gases, and gradient factors
+    pub profiles: Vec<ValidateRequest>,
+}
+
+/// Response payload from batch deco validation.
+#[derive(Debug, Serialize)]
+pub struct ValidateBatchResponse {
+    /// Tool identifier
+    pub tool: &'static str,
+    /// Tool version
+    pub tool_version: &'static str,
+    /// SHA256 hash of the whole batch request, for caching/idempotency
+    pub input_hash: String,
+    /// Per-profile results, aligned by index with the request's `profiles`.
+    /// A profile that failed validation has its own `error` set rather than
+    /// failing the whole batch.
+    pub results: Vec<ValidateResponse>,
+}
@@ -9,7 +9,7 @@ use axum::{
 
 use crate::AppState;
 
-use super::models::{ValidateRequest, ValidateResponse};
+use super::models::{ValidateBatchRequest, ValidateBatchResponse, ValidateRequest, ValidateResponse};
 use super::validator;
 
 /// Create the deco router with all endpoints.
@@ -17,6 +17,7 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/health", get(health))
         .route("/validate", post(validate))
+        .route("/validate-batch", post(validate_batch))
 }
 
 /// Health check for deco validation engine.
@@ -39,8 +40,11 @@ async fn validate(
     match validator::validate(
         &request.segments,
         &request.gas,
+        &request.deco_gases,
         request.gf_low,
         request.gf_high,
+        request.surface_pressure_mbar,
+        request.trace,
         &input_json,
     ) {
         Ok(response) => Ok(Json(response)),
@@ -54,3 +58,48 @@ async fn validate(
         )),
     }
 }
+
+/// Validate a batch of dive profiles in parallel, returning one result per
+/// profile aligned by index. A single bad profile doesn't fail the batch -
+/// its result just carries an `error` instead of a schedule.
+async fn validate_batch(Json(request): Json<ValidateBatchRequest>) -> Json<ValidateBatchResponse> {
+    let batch_json = serde_json::to_string(&request).unwrap_or_default();
+    let input_hash = validator::sha256_hex(&batch_json);
+
+    let handles: Vec<_> = request
+        .profiles
+        .into_iter()
+        .map(|profile| {
+            tokio::task::spawn_blocking(move || {
+                let profile_json = serde_json::to_string(&profile).unwrap_or_default();
+                validator::validate(
+                    &profile.segments,
+                    &profile.gas,
+                    &profile.deco_gases,
+                    profile.gf_low,
+                    profile.gf_high,
+                    profile.surface_pressure_mbar,
+                    profile.trace,
+                    &profile_json,
+                )
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        let response = match handle.await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => validator::error_response(e.to_string()),
+            Err(e) => validator::error_response(format!("validation task panicked: {}", e)),
+        };
+        results.push(response);
+    }
+
+    Json(ValidateBatchResponse {
+        tool: "diveops-deco-validate",
+        tool_version: "0.2.0",
+        input_hash,
+        results,
+    })
+}
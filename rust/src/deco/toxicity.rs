@@ -0,0 +1,145 @@
+//! Oxygen toxicity accounting (CNS% and OTU) for a dive profile.
+
+use super::models::{GasMix, Segment};
+
+/// NOAA single-exposure CNS table: (PO2 in bar, maximum exposure in minutes).
+/// Used to linearly interpolate the maximum allowable time at a given PO2.
+const NOAA_CNS_TABLE: &[(f64, f64)] = &[
+    (0.6, 720.0),
+    (0.7, 570.0),
+    (0.8, 450.0),
+    (0.9, 360.0),
+    (1.0, 300.0),
+    (1.1, 240.0),
+    (1.2, 210.0),
+    (1.3, 180.0),
+    (1.4, 150.0),
+    (1.5, 120.0),
+    (1.6, 45.0),
+];
+
+/// Result of walking a dive profile for oxygen-toxicity accounting.
+pub struct ToxicityResult {
+    /// Accumulated CNS oxygen toxicity, as a percentage of the single-exposure limit
+    pub cns_percent: f64,
+    /// Accumulated pulmonary oxygen toxicity units
+    pub otu: f64,
+    /// Warnings raised while walking the profile (e.g. CNS/PO2 exceeded safe limits)
+    pub warnings: Vec<String>,
+}
+
+/// Ambient pressure in bar at a given depth (meters), assuming 10m per atmosphere.
+fn ambient_bar(depth_m: f64) -> f64 {
+    1.0 + depth_m / 10.0
+}
+
+/// Maximum single-exposure time (minutes) at the given PO2, via linear
+/// interpolation of the NOAA table. PO2 below the table's lower bound
+/// contributes no CNS loading; PO2 above the upper bound uses the last entry.
+fn max_time_at_po2(po2: f64) -> f64 {
+    if po2 < NOAA_CNS_TABLE[0].0 {
+        return f64::INFINITY;
+    }
+    for window in NOAA_CNS_TABLE.windows(2) {
+        let (lo_po2, lo_min) = window[0];
+        let (hi_po2, hi_min) = window[1];
+        if po2 >= lo_po2 && po2 <= hi_po2 {
+            let t = (po2 - lo_po2) / (hi_po2 - lo_po2);
+            return lo_min + t * (hi_min - lo_min);
+        }
+    }
+    NOAA_CNS_TABLE.last().unwrap().1
+}
+
+/// Walk each segment and accumulate CNS% and OTU, using the gas assigned to
+/// each segment (see `Segment::gas_index`).
+pub fn compute(segments: &[Segment], gases: &[&GasMix]) -> ToxicityResult {
+    let mut cns_percent = 0.0;
+    let mut otu = 0.0;
+    let mut warnings = Vec::new();
+
+    for seg in segments {
+        let gas = gases[seg.gas_index.unwrap_or(0)];
+        let po2 = ambient_bar(seg.depth_m) * gas.o2;
+
+        if po2 > 1.6 {
+            warnings.push(format!(
+                "PO2 {:.2} bar at {:.1}m exceeds 1.6 bar safe limit",
+                po2, seg.depth_m
+            ));
+        }
+
+        if po2 >= 0.5 {
+            cns_percent += (seg.duration_min / max_time_at_po2(po2)) * 100.0;
+            otu += seg.duration_min * ((po2 - 0.5) / 0.5).powf(0.83);
+        }
+    }
+
+    if cns_percent > 100.0 {
+        warnings.push(format!("CNS {:.0}% exceeds 100% single-exposure limit", cns_percent));
+    }
+
+    ToxicityResult { cns_percent, otu, warnings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cns_and_otu_interpolated_between_table_entries() {
+        // EAN32 at 30m: PO2 = 1.28 bar, which sits 80% of the way from the
+        // 1.2 bar (210 min) to 1.3 bar (180 min) NOAA table entries.
+        let segments = vec![Segment { depth_m: 30.0, duration_min: 40.0, gas_index: None }];
+        let gas = GasMix { o2: 0.32, he: 0.0 };
+        let gases = vec![&gas];
+
+        let result = compute(&segments, &gases);
+
+        assert!((result.cns_percent - 21.505376).abs() < 1e-4);
+        assert!((result.otu - 57.856666).abs() < 1e-4);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_cns_zero_below_noaa_table_floor() {
+        // Air at 18m: PO2 = 0.588 bar, below the table's 0.6 bar floor, so it
+        // contributes no CNS loading even though it's above the 0.5 bar OTU
+        // threshold and does accrue OTU.
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 30.0, gas_index: None }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+        let gases = vec![&gas];
+
+        let result = compute(&segments, &gases);
+
+        assert_eq!(result.cns_percent, 0.0);
+        assert!((result.otu - 7.094091).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ppo2_over_limit_warns() {
+        // Pure O2 at 18m: PO2 = 2.8 bar, well past the 1.6 bar safe limit.
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 5.0, gas_index: None }];
+        let gas = GasMix { o2: 1.0, he: 0.0 };
+        let gases = vec![&gas];
+
+        let result = compute(&segments, &gases);
+
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("exceeds 1.6 bar safe limit"));
+    }
+
+    #[test]
+    fn test_cns_over_100_percent_warns() {
+        // A long exposure well past 1.6 bar racks up enough CNS% to cross
+        // the single-exposure limit and should warn about it too.
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 120.0, gas_index: None }];
+        let gas = GasMix { o2: 1.0, he: 0.0 };
+        let gases = vec![&gas];
+
+        let result = compute(&segments, &gases);
+
+        assert!(result.cns_percent > 100.0);
+        assert!(result.warnings.iter().any(|w| w.contains("exceeds 100%")));
+    }
+}
@@ -1,24 +1,62 @@
 //! Bühlmann ZHL-16C decompression validation logic.
 
-use dive_deco::{BuehlmannConfig, BuehlmannModel, Deco, DecoModel, DecoStageType, Gas};
+use dive_deco::{Deco, DecoStageType, Gas};
 use sha2::{Digest, Sha256};
 
-use super::models::{DecoStop, GasMix, Segment, ValidateResponse};
+use super::engine;
+use super::models::{
+    CompartmentTrace, DecoStop, GasMix, Segment, SegmentTrace, ValidateResponse,
+    SEA_LEVEL_PRESSURE_MBAR, SURFACE_PRESSURE_RANGE_MBAR,
+};
+use super::toxicity;
+
+/// Safe PO2 ceiling (bar) used to flag risky gas switches and to pick the
+/// gas assumed at each reported deco stop.
+const SAFE_SWITCH_PPO2_BAR: f64 = 1.6;
 
 /// Compute SHA256 hash of input string.
-fn sha256_hex(s: &str) -> String {
+pub(crate) fn sha256_hex(s: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(s.as_bytes());
     let digest = hasher.finalize();
     format!("sha256:{}", hex::encode(digest))
 }
 
+/// Build a `ValidateResponse` carrying only an error, for batch validation
+/// where one profile failing shouldn't fail the whole batch.
+pub(crate) fn error_response(message: String) -> ValidateResponse {
+    ValidateResponse {
+        tool: "diveops-deco-validate",
+        tool_version: "0.2.0",
+        model: "",
+        gf_low: 0.0,
+        gf_high: 0.0,
+        ceiling_m: 0.0,
+        tts_min: 0.0,
+        ndl_min: None,
+        deco_required: false,
+        stops: vec![],
+        max_depth_m: 0.0,
+        runtime_min: 0.0,
+        switch_depths_m: vec![],
+        surface_pressure_mbar: 0.0,
+        cns_percent: 0.0,
+        otu: 0.0,
+        trace: None,
+        input_hash: String::new(),
+        warnings: vec![],
+        error: Some(message),
+    }
+}
+
 /// Validation error types.
 #[derive(Debug)]
 pub enum ValidationError {
     NoSegments,
     InvalidGasFractions,
     GasFractionsExceedOne,
+    GasIndexOutOfRange { segment_index: usize, gas_index: usize },
+    InvalidSurfacePressure,
 }
 
 impl std::fmt::Display for ValidationError {
@@ -27,27 +65,75 @@ impl std::fmt::Display for ValidationError {
             Self::NoSegments => write!(f, "no segments provided"),
             Self::InvalidGasFractions => write!(f, "gas fractions must be between 0.0 and 1.0"),
             Self::GasFractionsExceedOne => write!(f, "gas fractions (O2 + He) exceed 1.0"),
+            Self::GasIndexOutOfRange { segment_index, gas_index } => write!(
+                f,
+                "segment {} references gas_index {} but no such gas was provided",
+                segment_index, gas_index
+            ),
+            Self::InvalidSurfacePressure => write!(
+                f,
+                "surface_pressure_mbar must be between {} and {} mbar",
+                SURFACE_PRESSURE_RANGE_MBAR.start(),
+                SURFACE_PRESSURE_RANGE_MBAR.end()
+            ),
         }
     }
 }
 
+/// Pick the gas assumed to be breathed at a deco stop: the richest
+/// available mix (highest O2) whose PO2 at `depth_m` doesn't exceed the
+/// safe limit, falling back to the bottom gas if every deco gas is too
+/// hot for this depth.
+fn gas_for_stop_depth(depth_m: f64, all_mixes: &[&GasMix]) -> GasMix {
+    all_mixes
+        .iter()
+        .filter(|mix| mix.o2 * (depth_m / 10.0 + 1.0) <= SAFE_SWITCH_PPO2_BAR)
+        .max_by(|a, b| a.o2.partial_cmp(&b.o2).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|mix| (*mix).clone())
+        .unwrap_or_else(|| all_mixes[0].clone())
+}
+
 /// Validate a dive profile and compute decompression status.
 pub fn validate(
     segments: &[Segment],
     gas: &GasMix,
+    deco_gases: &[GasMix],
     gf_low: f64,
     gf_high: f64,
+    surface_pressure_mbar: Option<f64>,
+    trace: bool,
     input_json: &str,
 ) -> Result<ValidateResponse, ValidationError> {
     // Basic validation
     if segments.is_empty() {
         return Err(ValidationError::NoSegments);
     }
-    if !(0.0..=1.0).contains(&gas.o2) || !(0.0..=1.0).contains(&gas.he) {
-        return Err(ValidationError::InvalidGasFractions);
+    let surface_pressure_mbar = surface_pressure_mbar.unwrap_or(SEA_LEVEL_PRESSURE_MBAR);
+    if !SURFACE_PRESSURE_RANGE_MBAR.contains(&surface_pressure_mbar) {
+        return Err(ValidationError::InvalidSurfacePressure);
     }
-    if gas.o2 + gas.he > 1.0 {
-        return Err(ValidationError::GasFractionsExceedOne);
+    for mix in std::iter::once(gas).chain(deco_gases.iter()) {
+        if !(0.0..=1.0).contains(&mix.o2) || !(0.0..=1.0).contains(&mix.he) {
+            return Err(ValidationError::InvalidGasFractions);
+        }
+        if mix.o2 + mix.he > 1.0 {
+            return Err(ValidationError::GasFractionsExceedOne);
+        }
+    }
+
+    // Combined gas list: bottom gas at index 0, then the supplied deco gases
+    let all_mixes: Vec<&GasMix> = std::iter::once(gas).chain(deco_gases.iter()).collect();
+    let all_gases: Vec<Gas> = all_mixes.iter().map(|m| Gas::new(m.o2, m.he)).collect();
+
+    for (i, seg) in segments.iter().enumerate() {
+        if let Some(idx) = seg.gas_index {
+            if idx >= all_gases.len() {
+                return Err(ValidationError::GasIndexOutOfRange {
+                    segment_index: i,
+                    gas_index: idx,
+                });
+            }
+        }
     }
 
     let input_hash = sha256_hex(input_json);
@@ -60,21 +146,70 @@ pub fn validate(
 
     let runtime_min: f64 = segments.iter().map(|s| s.duration_min).sum();
 
+    // Track the depths where the active gas changes (starting with the first
+    // segment's gas), for reporting back to the caller, and flag any switch that lands on a gas too rich to breathe safely
+    // at that depth.
+    let mut switch_depths_m: Vec<f64> = vec![];
+    let mut switch_warnings: Vec<String> = vec![];
+    let mut prev_gas_index: Option<usize> = None;
+    for seg in segments {
+        let idx = seg.gas_index.unwrap_or(0);
+        if prev_gas_index != Some(idx) {
+            switch_depths_m.push(seg.depth_m);
+
+            let mix = all_mixes[idx];
+            let ppo2 = mix.o2 * (seg.depth_m / 10.0 + 1.0);
+            if ppo2 > SAFE_SWITCH_PPO2_BAR {
+                switch_warnings.push(format!(
+                    "Gas switch at {:.1}m to {:.0}% O2 gives PO2 {:.2} bar, exceeding the {:.1} bar safe limit",
+                    seg.depth_m,
+                    mix.o2 * 100.0,
+                    ppo2,
+                    SAFE_SWITCH_PPO2_BAR
+                ));
+            }
+
+            prev_gas_index = Some(idx);
+        }
+    }
+
     // Convert gradient factors from fractions (0.0-1.0) to integers (0-100)
     let gf_low_int = (gf_low * 100.0).round() as u8;
     let gf_high_int = (gf_high * 100.0).round() as u8;
 
-    // Configure Bühlmann model with gradient factors
-    let config = BuehlmannConfig::new().gradient_factors(gf_low_int, gf_high_int);
-    let mut model = BuehlmannModel::new(config);
+    // Build the decompression engine (Bühlmann ZHL-16C)
+    let mut model = engine::build(gf_low_int, gf_high_int, surface_pressure_mbar);
 
-    // Create gas mix
-    let dive_gas = Gas::new(gas.o2, gas.he);
-
-    // Record each segment (step takes depth in meters, duration in seconds)
+    // Record each segment (step takes depth in meters, duration in seconds), using
+    // whichever gas the segment is assigned to support travel/deco gas switches.
+    // In trace mode, snapshot every compartment's tissue loading after each step
+    // so callers can see which compartment is leading/controlling the ceiling.
+    let mut segment_trace: Vec<SegmentTrace> = Vec::new();
+    let mut elapsed_min = 0.0;
     for seg in segments {
         let seconds = (seg.duration_min * 60.0).round() as usize;
-        model.step(&seg.depth_m, &seconds, &dive_gas);
+        let seg_gas = &all_gases[seg.gas_index.unwrap_or(0)];
+        model.step(&seg.depth_m, &seconds, seg_gas);
+        elapsed_min += seg.duration_min;
+
+        if trace {
+            let compartments = model
+                .compartments()
+                .iter()
+                .enumerate()
+                .map(|(i, c)| CompartmentTrace {
+                    compartment: (i + 1) as u8,
+                    n2_bar: c.n2_pressure,
+                    he_bar: c.he_pressure,
+                    ceiling_m: ((c.min_tolerable_amb_pressure - 1.0) * 10.0).max(0.0),
+                })
+                .collect();
+            segment_trace.push(SegmentTrace {
+                depth_m: seg.depth_m,
+                runtime_min: elapsed_min,
+                compartments,
+            });
+        }
     }
 
     // Get ceiling (meters) - this is the depth we cannot ascend above
@@ -94,9 +229,9 @@ pub fn validate(
         None
     };
 
-    // Calculate deco schedule and TTS
-    let available_gases = vec![dive_gas];
-    let Deco { deco_stages, tts } = model.deco(available_gases);
+    // Calculate deco schedule and TTS, letting the model pick the richest available
+    // gas (bottom + deco gases) for each stop
+    let Deco { deco_stages, tts } = model.deco(all_gases);
 
     // TTS is in seconds, convert to minutes
     let tts_min = tts as f64 / 60.0;
@@ -109,9 +244,14 @@ pub fn validate(
         .map(|stage| DecoStop {
             depth_m: stage.start_depth,
             duration_min: stage.duration as f64 / 60.0,
+            gas: gas_for_stop_depth(stage.start_depth, &all_mixes),
         })
         .collect();
 
+    let toxicity = toxicity::compute(segments, &all_mixes);
+    let mut warnings = toxicity.warnings;
+    warnings.extend(switch_warnings);
+
     Ok(ValidateResponse {
         tool: "diveops-deco-validate",
         tool_version: "0.2.0",
@@ -125,8 +265,13 @@ pub fn validate(
         stops,
         max_depth_m,
         runtime_min,
+        switch_depths_m,
+        surface_pressure_mbar,
+        cns_percent: toxicity.cns_percent,
+        otu: toxicity.otu,
+        trace: if trace { Some(segment_trace) } else { None },
         input_hash,
-        warnings: vec![],
+        warnings,
         error: None,
     })
 }
@@ -138,11 +283,11 @@ mod tests {
     #[test]
     fn test_no_deco_dive() {
         let segments = vec![
-            Segment { depth_m: 18.0, duration_min: 30.0 },
+            Segment { depth_m: 18.0, duration_min: 30.0, gas_index: None },
         ];
         let gas = GasMix { o2: 0.21, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}").unwrap();
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}").unwrap();
 
         assert!(!result.deco_required);
         assert!(result.ndl_min.is_some());
@@ -153,11 +298,11 @@ mod tests {
     #[test]
     fn test_deco_dive() {
         let segments = vec![
-            Segment { depth_m: 40.0, duration_min: 30.0 },
+            Segment { depth_m: 40.0, duration_min: 30.0, gas_index: None },
         ];
         let gas = GasMix { o2: 0.21, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}").unwrap();
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}").unwrap();
 
         assert!(result.deco_required);
         assert!(result.ndl_min.is_none());
@@ -170,48 +315,165 @@ mod tests {
         let segments: Vec<Segment> = vec![];
         let gas = GasMix { o2: 0.21, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}");
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}");
         assert!(matches!(result, Err(ValidationError::NoSegments)));
     }
 
     #[test]
     fn test_invalid_gas_fractions() {
-        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0 }];
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
         let gas = GasMix { o2: 1.5, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}");
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}");
         assert!(matches!(result, Err(ValidationError::InvalidGasFractions)));
     }
 
     #[test]
     fn test_gas_fractions_exceed_one() {
-        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0 }];
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
         let gas = GasMix { o2: 0.6, he: 0.5 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}");
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}");
         assert!(matches!(result, Err(ValidationError::GasFractionsExceedOne)));
     }
 
     #[test]
     fn test_ean32() {
         let segments = vec![
-            Segment { depth_m: 30.0, duration_min: 40.0 },
+            Segment { depth_m: 30.0, duration_min: 40.0, gas_index: None },
         ];
         let gas = GasMix { o2: 0.32, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, "{}").unwrap();
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}").unwrap();
 
         // EAN32 at 30m for 40min should be close to NDL or just in deco
         assert_eq!(result.max_depth_m, 30.0);
         assert_eq!(result.runtime_min, 40.0);
     }
 
+    #[test]
+    fn test_gas_switch_recorded_at_switch_depth() {
+        // Bottom gas is air (index 0); a single EAN50 deco gas sits at index 1.
+        // The profile explicitly switches onto it at 21m.
+        let segments = vec![
+            Segment { depth_m: 40.0, duration_min: 25.0, gas_index: Some(0) },
+            Segment { depth_m: 21.0, duration_min: 5.0, gas_index: Some(1) },
+        ];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+        let deco_gases = vec![GasMix { o2: 0.50, he: 0.0 }];
+
+        let result = validate(&segments, &gas, &deco_gases, 0.40, 0.85, None, false, "{}").unwrap();
+
+        assert_eq!(result.switch_depths_m, vec![40.0, 21.0]);
+        assert!(result.deco_required);
+    }
+
+    #[test]
+    fn test_deco_stop_assumes_richest_safe_deco_gas() {
+        // EAN50's PO2 stays under the 1.6 bar safe limit at any stop shallower
+        // than 22m, so the shallowest stop should be reported on it rather
+        // than the bottom air.
+        let segments = vec![Segment { depth_m: 40.0, duration_min: 30.0, gas_index: Some(0) }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+        let deco_gases = vec![GasMix { o2: 0.50, he: 0.0 }];
+
+        let result = validate(&segments, &gas, &deco_gases, 0.40, 0.85, None, false, "{}").unwrap();
+
+        assert!(!result.stops.is_empty());
+        let shallowest = result
+            .stops
+            .iter()
+            .min_by(|a, b| a.depth_m.partial_cmp(&b.depth_m).unwrap())
+            .unwrap();
+        assert_eq!(shallowest.gas.o2, 0.50);
+    }
+
+    #[test]
+    fn test_gas_index_out_of_range_error() {
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: Some(1) }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}");
+        assert!(matches!(
+            result,
+            Err(ValidationError::GasIndexOutOfRange { segment_index: 0, gas_index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_altitude_surface_pressure_is_reported_and_shortens_ndl() {
+        // Lower ambient pressure at altitude makes the same depth/time load
+        // tissues relatively harder, so the no-deco limit should shrink
+        // compared to sea level.
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let sea_level = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}").unwrap();
+        let altitude = validate(&segments, &gas, &[], 0.40, 0.85, Some(700.0), false, "{}").unwrap();
+
+        assert_eq!(sea_level.surface_pressure_mbar, SEA_LEVEL_PRESSURE_MBAR);
+        assert_eq!(altitude.surface_pressure_mbar, 700.0);
+        assert!(altitude.ndl_min.unwrap() < sea_level.ndl_min.unwrap());
+    }
+
+    #[test]
+    fn test_surface_pressure_too_low_rejected() {
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, Some(100.0), false, "{}");
+        assert!(matches!(result, Err(ValidationError::InvalidSurfacePressure)));
+    }
+
+    #[test]
+    fn test_surface_pressure_too_high_rejected() {
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, Some(2000.0), false, "{}");
+        assert!(matches!(result, Err(ValidationError::InvalidSurfacePressure)));
+    }
+
+    #[test]
+    fn test_trace_emits_16_compartments_per_segment() {
+        let segments = vec![
+            Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None },
+            Segment { depth_m: 12.0, duration_min: 5.0, gas_index: None },
+        ];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, true, "{}").unwrap();
+
+        let trace = result.trace.expect("trace was requested");
+        assert_eq!(trace.len(), 2);
+        for snapshot in &trace {
+            assert_eq!(snapshot.compartments.len(), 16);
+            let numbers: Vec<u8> = snapshot.compartments.iter().map(|c| c.compartment).collect();
+            assert_eq!(numbers, (1..=16).collect::<Vec<u8>>());
+            for c in &snapshot.compartments {
+                assert!(c.ceiling_m >= 0.0);
+            }
+        }
+        assert_eq!(trace[0].runtime_min, 10.0);
+        assert_eq!(trace[1].runtime_min, 15.0);
+    }
+
+    #[test]
+    fn test_trace_omitted_when_not_requested() {
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
+        let gas = GasMix { o2: 0.21, he: 0.0 };
+
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, "{}").unwrap();
+
+        assert!(result.trace.is_none());
+    }
+
     #[test]
     fn test_input_hash() {
-        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0 }];
+        let segments = vec![Segment { depth_m: 18.0, duration_min: 10.0, gas_index: None }];
         let gas = GasMix { o2: 0.21, he: 0.0 };
 
-        let result = validate(&segments, &gas, 0.40, 0.85, r#"{"test": true}"#).unwrap();
+        let result = validate(&segments, &gas, &[], 0.40, 0.85, None, false, r#"{"test": true}"#).unwrap();
 
         assert!(result.input_hash.starts_with("sha256:"));
         assert_eq!(result.input_hash.len(), 7 + 64); // "sha256:" + 64 hex chars
@@ -6,15 +6,18 @@
 use axum::{
     extract::State,
     response::{IntoResponse, Json},
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
-use tower_http::{compression::CompressionLayer, services::ServeDir, trace::TraceLayer};
+use std::sync::{Arc, RwLock};
+use tower_http::{services::ServeDir, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+pub mod blog;
 mod cache;
+mod compression;
 mod db;
 pub mod deco;
 mod error;
@@ -22,6 +25,7 @@ mod models;
 pub mod pricing;
 mod routes;
 
+use blog::SearchIndex;
 use cache::{start_cache_warmer, AppCache};
 
 /// Application state shared across all handlers
@@ -29,6 +33,10 @@ use cache::{start_cache_warmer, AppCache};
 pub struct AppState {
     pub db: PgPool,
     pub cache: AppCache,
+    /// Embedded full-text search index over published blog posts
+    pub search_index: blog::SearchState,
+    /// Rendered Open Graph share images, cached by slug + snapshot version
+    pub og_image_cache: blog::OgImageCache,
 }
 
 #[tokio::main]
@@ -73,11 +81,16 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Database connected successfully");
 
+    // Load (or start) the blog search index
+    let search_index = Arc::new(RwLock::new(SearchIndex::load_or_default(&blog::search_index_path())));
+
     // Create cache and application state
     let cache = AppCache::new();
     let state = AppState {
         db: pool.clone(),
         cache: cache.clone(),
+        search_index,
+        og_image_cache: blog::og_image::new_cache(),
     };
 
     // Start background cache warmer
@@ -93,6 +106,12 @@ async fn main() -> anyhow::Result<()> {
         .route("/health", get(health_check))
         .route("/health/cache", get(cache_stats))
         // Blog routes
+        .route("/blog/search", get(blog::search_handler))
+        .route("/blog/:slug/og.png", get(blog::og_image_handler))
+        // Publish/unpublish webhook, called by Django to keep the search
+        // index (and Mastodon announcements) in sync with what's live
+        .route("/internal/blog/publish", post(blog::publish_handler))
+        .route("/internal/blog/unpublish/:slug", post(blog::unpublish_handler))
         .route("/blog/", get(routes::blog::list))
         .route("/blog/:slug/", get(routes::blog::detail))
         .route("/blog/category/:category/", get(routes::blog::by_category))
@@ -110,7 +129,7 @@ async fn main() -> anyhow::Result<()> {
         .nest_service("/static", ServeDir::new("static"))
         // State and middleware
         .with_state(state)
-        .layer(CompressionLayer::new())
+        .layer(compression::layer())
         .layer(TraceLayer::new_for_http());
 
     // Start server
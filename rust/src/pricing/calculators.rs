@@ -0,0 +1,233 @@
+//! Pricing calculation core.
+//!
+//! All monetary math happens in integer minor units (cents) so that shared-cost
+//! allocation and per-diver totals don't accumulate the float drift that comes
+//! from repeatedly summing and dividing `f64` dollar amounts. Amounts only
+//! touch `f64` at the request boundary and are formatted back to decimal
+//! strings before crossing back out over JSON.
+
+/// ISO 4217 currencies with no minor unit (their smallest denomination is
+/// the whole unit, e.g. the Japanese yen).
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &["JPY", "KRW", "VND", "CLP"];
+
+/// ISO 4217 currencies whose minor unit is a thousandth rather than a
+/// hundredth (e.g. the Bahraini dinar's fils).
+const THREE_DECIMAL_CURRENCIES: &[&str] = &["BHD", "KWD", "OMR", "TND"];
+
+/// Number of decimal digits `currency`'s minor unit represents. Defaults to
+/// 2 (the common case, including USD/MXN) for anything not listed above.
+fn decimal_places(currency: &str) -> u32 {
+    let upper = currency.to_uppercase();
+    if ZERO_DECIMAL_CURRENCIES.contains(&upper.as_str()) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&upper.as_str()) {
+        3
+    } else {
+        2
+    }
+}
+
+/// Convert a decimal amount (e.g. dollars) to integer minor units (cents),
+/// rounding to the nearest minor unit for `currency`.
+fn to_minor_units(amount: f64, currency: &str) -> i64 {
+    let scale = 10f64.powi(decimal_places(currency) as i32);
+    (amount * scale).round() as i64
+}
+
+/// Format integer minor units back to a decimal string (e.g. "12.34"),
+/// using as many decimal places as `currency` calls for.
+fn format_minor_units(units: i64, currency: &str) -> String {
+    let places = decimal_places(currency);
+    if places == 0 {
+        return units.to_string();
+    }
+    let negative = units < 0;
+    let scale = 10i64.pow(places);
+    let abs = units.unsigned_abs() as i64;
+    let sign = if negative { "-" } else { "" };
+    format!("{}{}.{:0width$}", sign, abs / scale, abs % scale, width = places as usize)
+}
+
+/// Split `total_cents` across `diver_count` divers using largest-remainder
+/// apportionment: every diver gets `total_cents / diver_count` cents, and the
+/// leftover `total_cents % diver_count` cents are distributed one each to the
+/// first divers, so the amounts always sum exactly back to `total_cents`.
+fn split_largest_remainder(total_cents: i64, diver_count: u32) -> Vec<i64> {
+    if diver_count == 0 {
+        return vec![];
+    }
+    let diver_count = diver_count as i64;
+    let base = total_cents.div_euclid(diver_count);
+    let remainder = total_cents.rem_euclid(diver_count) as usize;
+    (0..diver_count as usize)
+        .map(|i| base + if i < remainder { 1 } else { 0 })
+        .collect()
+}
+
+/// A single pricing line to be rolled into totals.
+pub struct PricingLineInput {
+    pub key: String,
+    /// "shared" splits this line's cost/charge across all divers;
+    /// "per_diver" applies it to each diver directly.
+    pub allocation: String,
+    pub shop_cost_amount: f64,
+    pub customer_charge_amount: f64,
+}
+
+/// A rented equipment line, billed per unit.
+pub struct EquipmentRentalInput {
+    pub unit_cost_amount: f64,
+    pub unit_charge_amount: f64,
+    pub quantity: u32,
+}
+
+/// Result of allocating a shared cost among divers.
+pub struct AllocationResult {
+    /// The base (floor) per-diver amount, as a decimal string
+    pub per_diver: String,
+    /// Each diver's exact allocation, summing exactly to the input total
+    pub amounts: Vec<String>,
+}
+
+/// Allocate a shared cost among divers using largest-remainder apportionment.
+pub fn allocate_shared_costs(shared_total: f64, diver_count: u32, currency: &str) -> AllocationResult {
+    let total_cents = to_minor_units(shared_total, currency);
+    let per_diver_cents = split_largest_remainder(total_cents, diver_count);
+    let base_cents = if diver_count == 0 { 0 } else { total_cents.div_euclid(diver_count as i64) };
+
+    AllocationResult {
+        per_diver: format_minor_units(base_cents, currency),
+        amounts: per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+    }
+}
+
+/// Result of calculating pricing totals from lines (and optional rentals).
+pub struct TotalsResult {
+    pub shared_cost: String,
+    pub shared_charge: String,
+    pub per_diver_cost: String,
+    pub per_diver_charge: String,
+    pub shared_cost_per_diver: Vec<String>,
+    pub shared_charge_per_diver: Vec<String>,
+    pub total_cost_per_diver: Vec<String>,
+    pub total_charge_per_diver: Vec<String>,
+    pub margin_per_diver: Vec<String>,
+    pub diver_count: u32,
+}
+
+/// Calculate pricing totals from lines, splitting shared-allocation lines
+/// across divers with largest-remainder apportionment and adding any
+/// per-diver lines and equipment rentals on top.
+pub fn calculate_totals(
+    lines: &[PricingLineInput],
+    diver_count: u32,
+    currency: &str,
+    rentals: Option<&[EquipmentRentalInput]>,
+) -> TotalsResult {
+    let mut shared_cost_cents = 0i64;
+    let mut shared_charge_cents = 0i64;
+    let mut per_diver_cost_cents = 0i64;
+    let mut per_diver_charge_cents = 0i64;
+
+    for line in lines {
+        let cost_cents = to_minor_units(line.shop_cost_amount, currency);
+        let charge_cents = to_minor_units(line.customer_charge_amount, currency);
+        if line.allocation == "per_diver" {
+            per_diver_cost_cents += cost_cents;
+            per_diver_charge_cents += charge_cents;
+        } else {
+            shared_cost_cents += cost_cents;
+            shared_charge_cents += charge_cents;
+        }
+    }
+
+    if let Some(rentals) = rentals {
+        for rental in rentals {
+            shared_cost_cents += to_minor_units(rental.unit_cost_amount, currency) * rental.quantity as i64;
+            shared_charge_cents += to_minor_units(rental.unit_charge_amount, currency) * rental.quantity as i64;
+        }
+    }
+
+    let shared_cost_per_diver_cents = split_largest_remainder(shared_cost_cents, diver_count);
+    let shared_charge_per_diver_cents = split_largest_remainder(shared_charge_cents, diver_count);
+
+    let total_cost_per_diver_cents: Vec<i64> = shared_cost_per_diver_cents
+        .iter()
+        .map(|c| c + per_diver_cost_cents)
+        .collect();
+    let total_charge_per_diver_cents: Vec<i64> = shared_charge_per_diver_cents
+        .iter()
+        .map(|c| c + per_diver_charge_cents)
+        .collect();
+    let margin_per_diver_cents: Vec<i64> = total_cost_per_diver_cents
+        .iter()
+        .zip(total_charge_per_diver_cents.iter())
+        .map(|(cost, charge)| charge - cost)
+        .collect();
+
+    TotalsResult {
+        shared_cost: format_minor_units(shared_cost_cents, currency),
+        shared_charge: format_minor_units(shared_charge_cents, currency),
+        per_diver_cost: format_minor_units(per_diver_cost_cents, currency),
+        per_diver_charge: format_minor_units(per_diver_charge_cents, currency),
+        shared_cost_per_diver: shared_cost_per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+        shared_charge_per_diver: shared_charge_per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+        total_cost_per_diver: total_cost_per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+        total_charge_per_diver: total_charge_per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+        margin_per_diver: margin_per_diver_cents.iter().map(|c| format_minor_units(*c, currency)).collect(),
+        diver_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_shared_costs_sums_exactly() {
+        let result = allocate_shared_costs(100.0, 3, "USD");
+        let total: i64 = result
+            .amounts
+            .iter()
+            .map(|s| (s.replace('.', "").parse::<i64>().unwrap()))
+            .sum();
+        assert_eq!(total, 10000);
+        assert_eq!(result.amounts, vec!["33.34", "33.33", "33.33"]);
+    }
+
+    #[test]
+    fn test_allocate_shared_costs_zero_divers() {
+        let result = allocate_shared_costs(100.0, 0, "USD");
+        assert!(result.amounts.is_empty());
+    }
+
+    #[test]
+    fn test_calculate_totals_per_diver_lines_not_split() {
+        let lines = vec![PricingLineInput {
+            key: "boat".to_string(),
+            allocation: "per_diver".to_string(),
+            shop_cost_amount: 10.0,
+            customer_charge_amount: 20.0,
+        }];
+        let result = calculate_totals(&lines, 4, "USD", None);
+        assert_eq!(result.per_diver_cost, "10.00");
+        assert_eq!(result.total_cost_per_diver, vec!["10.00", "10.00", "10.00", "10.00"]);
+        assert_eq!(result.margin_per_diver, vec!["10.00", "10.00", "10.00", "10.00"]);
+    }
+
+    #[test]
+    fn test_allocate_shared_costs_zero_decimal_currency() {
+        // JPY has no minor unit - amounts should come back with no decimal point.
+        let result = allocate_shared_costs(10000.0, 3, "JPY");
+        assert_eq!(result.per_diver, "3333");
+        assert_eq!(result.amounts, vec!["3334", "3333", "3333"]);
+    }
+
+    #[test]
+    fn test_allocate_shared_costs_three_decimal_currency() {
+        // BHD's minor unit is the fils, a thousandth of a dinar.
+        let result = allocate_shared_costs(10.0, 4, "BHD");
+        assert_eq!(result.per_diver, "2.500");
+        assert_eq!(result.amounts, vec!["2.500", "2.500", "2.500", "2.500"]);
+    }
+}
@@ -0,0 +1,29 @@
+//! Response payloads for the pricing API.
+//!
+//! Monetary amounts are serialized as decimal strings (e.g. `"12.34"`) rather
+//! than floats, since the calculators do all of their math in integer minor
+//! units and we don't want to reintroduce float error at the JSON boundary.
+
+use serde::Serialize;
+
+/// Response for `/allocate`.
+#[derive(Debug, Serialize)]
+pub struct AllocationResponse {
+    pub per_diver: String,
+    pub amounts: Vec<String>,
+}
+
+/// Response for `/totals`.
+#[derive(Debug, Serialize)]
+pub struct PricingTotalsResponse {
+    pub shared_cost: String,
+    pub shared_charge: String,
+    pub per_diver_cost: String,
+    pub per_diver_charge: String,
+    pub shared_cost_per_diver: Vec<String>,
+    pub shared_charge_per_diver: Vec<String>,
+    pub total_cost_per_diver: Vec<String>,
+    pub total_charge_per_diver: Vec<String>,
+    pub margin_per_diver: Vec<String>,
+    pub diver_count: u32,
+}
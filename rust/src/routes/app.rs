@@ -1,6 +1,7 @@
 //! App download page route handler
 
 use askama::Template;
+use aws_sdk_s3::presigning::PresigningConfig;
 use axum::response::Html;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use image::{ImageBuffer, Luma};
@@ -9,9 +10,13 @@ use std::cmp::Ordering;
 use std::fs;
 use std::io::Cursor;
 use std::path::Path;
+use std::time::Duration;
 
 use crate::error::Result;
 
+/// Default presigned URL lifetime for the S3-backed APK storage.
+const DEFAULT_PRESIGN_EXPIRY_SECS: u64 = 3600;
+
 /// Semantic version for APK files (supports suffixes like -alpha, -beta, -rc1)
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct Version {
@@ -88,6 +93,16 @@ struct ApkInfo {
     version: Version,
 }
 
+/// A located APK, already resolved to a download URL - a plain static
+/// path for the filesystem backend, or a presigned, expiring URL for
+/// the S3-compatible backend.
+#[derive(Debug)]
+struct ResolvedApk {
+    filename: String,
+    version: Version,
+    download_url: String,
+}
+
 /// Download page template
 #[derive(Template)]
 #[template(path = "app/download.html")]
@@ -132,6 +147,99 @@ fn find_latest_apk() -> Option<ApkInfo> {
     apks.into_iter().next()
 }
 
+/// Find the latest APK on the filesystem, building a plain, permanent
+/// static URL for it.
+fn find_latest_apk_filesystem(base_url: &str) -> Option<ResolvedApk> {
+    let apk = find_latest_apk()?;
+    let download_url = format!("{}/static/downloads/{}", base_url, apk.filename);
+    Some(ResolvedApk {
+        filename: apk.filename,
+        version: apk.version,
+        download_url,
+    })
+}
+
+/// Find the latest APK in an S3-compatible bucket (DigitalOcean Spaces,
+/// MinIO, ...), reusing `Version::parse`/`Ord` to pick the newest, and
+/// hand back a presigned, expiring download URL instead of a permanent
+/// link.
+async fn find_latest_apk_s3() -> Option<ResolvedApk> {
+    let bucket = std::env::var("APK_S3_BUCKET").ok()?;
+    let region = std::env::var("APK_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+    let expiry_secs = std::env::var("APK_PRESIGN_EXPIRY_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_PRESIGN_EXPIRY_SECS);
+
+    let shared_config = aws_config::from_env()
+        .region(aws_sdk_s3::config::Region::new(region))
+        .load()
+        .await;
+    let mut config_builder = aws_sdk_s3::config::Builder::from(&shared_config).force_path_style(true);
+    if let Ok(endpoint) = std::env::var("APK_S3_ENDPOINT") {
+        config_builder = config_builder.endpoint_url(endpoint);
+    }
+    let client = aws_sdk_s3::Client::from_conf(config_builder.build());
+
+    let listed = client
+        .list_objects_v2()
+        .bucket(&bucket)
+        .prefix("buceo-")
+        .send()
+        .await
+        .map_err(|e| tracing::error!("Failed to list APK objects in S3 bucket {}: {}", bucket, e))
+        .ok()?;
+
+    let mut apks: Vec<(String, Version)> = listed
+        .contents()
+        .iter()
+        .filter_map(|object| {
+            let key = object.key()?;
+            let filename = key.rsplit('/').next().unwrap_or(key);
+
+            if filename.starts_with("buceo-") && filename.ends_with(".apk") {
+                let version_str = filename.strip_prefix("buceo-")?.strip_suffix(".apk")?;
+                let version = Version::parse(version_str)?;
+                Some((key.to_string(), version))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Sort by version descending, take the latest
+    apks.sort_by(|a, b| b.1.cmp(&a.1));
+    let (key, version) = apks.into_iter().next()?;
+    let filename = key.rsplit('/').next().unwrap_or(&key).to_string();
+
+    let presigning_config = PresigningConfig::expires_in(Duration::from_secs(expiry_secs))
+        .map_err(|e| tracing::error!("Invalid APK presign expiry: {}", e))
+        .ok()?;
+    let presigned = client
+        .get_object()
+        .bucket(&bucket)
+        .key(&key)
+        .presigned(presigning_config)
+        .await
+        .map_err(|e| tracing::error!("Failed to presign APK download URL for {}: {}", key, e))
+        .ok()?;
+
+    Some(ResolvedApk {
+        filename,
+        version,
+        download_url: presigned.uri().to_string(),
+    })
+}
+
+/// Find the latest APK using the storage backend selected by
+/// `APK_STORAGE_BACKEND` (`s3` or `filesystem`, default `filesystem`).
+async fn resolve_latest_apk(base_url: &str) -> Option<ResolvedApk> {
+    match std::env::var("APK_STORAGE_BACKEND").as_deref() {
+        Ok("s3") => find_latest_apk_s3().await,
+        _ => find_latest_apk_filesystem(base_url),
+    }
+}
+
 /// Generate QR code as base64 PNG data URI
 fn generate_qr_code(url: &str) -> String {
     let code = match QrCode::new(url.as_bytes()) {
@@ -171,14 +279,15 @@ fn generate_qr_code(url: &str) -> String {
 pub async fn download() -> Result<Html<String>> {
     let base_url = std::env::var("BASE_URL").unwrap_or_else(|_| "https://happydiving.mx".to_string());
 
-    let (has_apk, version, filename, download_url, qr_code_data) = match find_latest_apk() {
+    let (has_apk, version, filename, download_url, qr_code_data) = match resolve_latest_apk(&base_url).await {
         Some(apk) => {
-            let download_url = format!("{}/static/downloads/{}", base_url, apk.filename);
-            let qr_code_data = generate_qr_code(&download_url);
-            (true, apk.version.to_string(), apk.filename, download_url, qr_code_data)
+            // Encode the QR from the same URL we show the link for, so
+            // scanning the code and clicking the link always agree.
+            let qr_code_data = generate_qr_code(&apk.download_url);
+            (true, apk.version.to_string(), apk.filename, apk.download_url, qr_code_data)
         }
         None => {
-            tracing::info!("No APK files found in downloads directory");
+            tracing::info!("No APK files found");
             (false, String::new(), String::new(), String::new(), String::new())
         }
     };
@@ -193,3 +302,68 @@ pub async fn download() -> Result<Html<String>> {
 
     Ok(Html(template.render().unwrap()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parse_valid() {
+        let v = Version::parse("1.2.3").unwrap();
+        assert_eq!(v.major, 1);
+        assert_eq!(v.minor, 2);
+        assert_eq!(v.patch, 3);
+        assert_eq!(v.suffix, None);
+    }
+
+    #[test]
+    fn test_version_parse_with_suffix() {
+        let v = Version::parse("0.1.0-alpha").unwrap();
+        assert_eq!(v.major, 0);
+        assert_eq!(v.minor, 1);
+        assert_eq!(v.patch, 0);
+        assert_eq!(v.suffix, Some("alpha".to_string()));
+    }
+
+    #[test]
+    fn test_version_parse_rejects_wrong_segment_count() {
+        assert!(Version::parse("1.2").is_none());
+        assert!(Version::parse("1.2.3.4").is_none());
+    }
+
+    #[test]
+    fn test_version_parse_rejects_non_numeric_segments() {
+        assert!(Version::parse("1.x.3").is_none());
+        assert!(Version::parse("latest").is_none());
+    }
+
+    #[test]
+    fn test_version_ordering_major_minor_patch() {
+        let older = Version::parse("1.2.3").unwrap();
+        let newer = Version::parse("1.3.0").unwrap();
+        assert!(newer > older);
+        assert!(Version::parse("2.0.0").unwrap() > newer);
+    }
+
+    #[test]
+    fn test_version_ordering_suffix_before_release() {
+        // A release (no suffix) outranks any pre-release of the same
+        // major.minor.patch.
+        let alpha = Version::parse("1.0.0-alpha").unwrap();
+        let release = Version::parse("1.0.0").unwrap();
+        assert!(release > alpha);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_latest_apk_s3_backend_without_bucket_returns_none() {
+        // With the s3 backend selected but APK_S3_BUCKET unset,
+        // find_latest_apk_s3 should bail out before making any network call.
+        std::env::set_var("APK_STORAGE_BACKEND", "s3");
+        std::env::remove_var("APK_S3_BUCKET");
+
+        let result = resolve_latest_apk("https://happydiving.mx").await;
+
+        std::env::remove_var("APK_STORAGE_BACKEND");
+        assert!(result.is_none());
+    }
+}